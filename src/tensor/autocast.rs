@@ -0,0 +1,41 @@
+//! Automatic mixed-precision (autocast) regions, mirroring the `no_grad`
+//! family re-exported alongside this module.
+use std::os::raw::c_int;
+
+fn autocast_is_enabled() -> bool {
+    unsafe { torch_sys::at_autocast_is_enabled() != 0 }
+}
+
+fn autocast_set_enabled(b: bool) -> bool {
+    let was_enabled = autocast_is_enabled();
+    unsafe { torch_sys::at_autocast_set_enabled(b as c_int) };
+    was_enabled
+}
+
+/// An RAII guard toggling libtorch's autocast region for as long as it is
+/// held, restoring the previous setting on drop. Ops run inside the region
+/// are cast to fp16/bf16 while accumulations stay in fp32.
+pub struct AutocastGuard {
+    prev_enabled: bool,
+}
+
+/// Enables (or disables) autocast and returns a guard that restores the
+/// previous setting when dropped.
+pub fn autocast_guard(enabled: bool) -> AutocastGuard {
+    let prev_enabled = autocast_set_enabled(enabled);
+    AutocastGuard { prev_enabled }
+}
+
+impl Drop for AutocastGuard {
+    fn drop(&mut self) {
+        let _enabled = autocast_set_enabled(self.prev_enabled);
+    }
+}
+
+/// Runs `f` with autocast set to `enabled`, restoring the previous setting
+/// once `f` returns -- the same shape as `no_grad`, so a model's
+/// `forward_t` can be wrapped directly.
+pub fn autocast<T, F: FnOnce() -> T>(enabled: bool, f: F) -> T {
+    let _guard = autocast_guard(enabled);
+    f()
+}
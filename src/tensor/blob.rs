@@ -0,0 +1,78 @@
+//! Zero-copy construction of a [`Tensor`] from memory owned by the caller.
+//!
+//! Unlike the `From<&[T]>` impl in the parent `tensor` module, which always goes through
+//! [`Tensor::of_slice`] and copies, [`Tensor::from_borrowed_blob`] wraps an
+//! existing buffer directly -- the caller is responsible for the invariants
+//! laid out in its safety section.
+use super::Tensor;
+use crate::{Device, Kind};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::os::raw::c_void;
+
+/// A [`Tensor`] that borrows its storage from an external buffer instead of
+/// owning a copy of it. Derefs to `Tensor` so it can be used anywhere a
+/// `&Tensor` is expected.
+///
+/// The lifetime `'a` ties this wrapper to the buffer it was built from (it
+/// is inferred from the `&'a [u8]` passed to [`Tensor::from_borrowed_blob`],
+/// so the borrow checker rejects the wrapper outliving its source), but
+/// nothing stops the buffer from being mutated out from under a live
+/// `BorrowedTensor` -- see the safety note there.
+pub struct BorrowedTensor<'a> {
+    tensor: Tensor,
+    _phantom: PhantomData<&'a [u8]>,
+}
+
+impl<'a> Deref for BorrowedTensor<'a> {
+    type Target = Tensor;
+
+    fn deref(&self) -> &Tensor {
+        &self.tensor
+    }
+}
+
+impl Tensor {
+    /// Wraps an external, borrowed buffer as a tensor without copying its
+    /// contents. `shape` and `strides` are both expressed in elements (not
+    /// bytes), mirroring libtorch's own `from_blob`, so sources with
+    /// arbitrary (including non-contiguous) layouts can be wrapped as-is.
+    ///
+    /// # Safety
+    ///
+    /// `data` must be valid for reads of `kind`-typed elements addressed by
+    /// `shape`/`strides` for as long as the returned [`BorrowedTensor`] (and
+    /// anything cloned from it) is alive. No copy is made: the tensor reads
+    /// directly from `data`, so mutating the backing storage while the
+    /// tensor is still in use is undefined behavior. Taking `data` as a
+    /// `&'a [u8]`, rather than a raw pointer, is what lets the borrow
+    /// checker tie the returned [`BorrowedTensor`]'s lifetime to the actual
+    /// source buffer instead of letting the caller pick `'a` freely.
+    ///
+    /// `device` must match where `data` actually resides: `data` is always a
+    /// host slice, so `device` must be `Device::Cpu` unless the bytes behind
+    /// it genuinely point at device memory (e.g. a pinned/mapped buffer) --
+    /// passing a CUDA device over a plain host slice makes libtorch read it
+    /// as a device pointer, which is undefined behavior.
+    pub unsafe fn from_borrowed_blob<'a>(
+        data: &'a [u8],
+        shape: &[i64],
+        strides: &[i64],
+        kind: Kind,
+        device: Device,
+    ) -> BorrowedTensor<'a> {
+        let c_tensor = torch_sys::at_from_blob(
+            data.as_ptr() as *const c_void,
+            shape.as_ptr(),
+            shape.len() as i32,
+            strides.as_ptr(),
+            strides.len() as i32,
+            kind.c_int(),
+            device.c_int(),
+        );
+        BorrowedTensor {
+            tensor: Tensor::from_ptr(c_tensor),
+            _phantom: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! A `Display` impl that renders tensor values for arbitrary rank, with
+//! NumPy-like edge-item truncation for large tensors.
+//!
+//! The existing `Debug` impl only prints values for small (<10 element)
+//! 0/1-D tensors and otherwise degrades to `Tensor[shape, kind]`; this
+//! module keeps that compact `Debug` output for logging, while giving
+//! `Display` a real, configurable, human-readable rendering.
+use super::Tensor;
+use crate::Kind;
+use std::fmt;
+use std::sync::{OnceLock, RwLock};
+
+/// Options controlling how [`Tensor`]'s `Display` impl renders values,
+/// modeled on NumPy's `set_printoptions`.
+#[derive(Debug, Clone, Copy)]
+pub struct PrintOptions {
+    /// Total number of elements above which a tensor is summarized instead
+    /// of printed in full.
+    pub threshold: usize,
+    /// Number of items shown at the start and end of each summarized
+    /// dimension before an `...` is inserted.
+    pub edge_items: usize,
+    /// Number of digits of precision for floating point output.
+    pub precision: usize,
+    /// Whether to print floating point values in scientific notation.
+    pub sci_mode: bool,
+    /// The preferred number of characters per line before wrapping the
+    /// innermost dimension onto a new line.
+    pub line_width: usize,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        PrintOptions {
+            threshold: 1000,
+            edge_items: 3,
+            precision: 4,
+            sci_mode: false,
+            line_width: 80,
+        }
+    }
+}
+
+impl PrintOptions {
+    /// Starts a [`PrintOptionsBuilder`] seeded with [`PrintOptions::default`]
+    /// (not with the currently installed global options).
+    pub fn builder() -> PrintOptionsBuilder {
+        PrintOptionsBuilder { options: PrintOptions::default() }
+    }
+}
+
+/// Builds a [`PrintOptions`] value and, once satisfied, installs it as the
+/// process-wide default used by `Tensor`'s `Display` impl.
+pub struct PrintOptionsBuilder {
+    options: PrintOptions,
+}
+
+impl PrintOptionsBuilder {
+    pub fn threshold(mut self, threshold: usize) -> Self {
+        self.options.threshold = threshold;
+        self
+    }
+
+    pub fn edge_items(mut self, edge_items: usize) -> Self {
+        self.options.edge_items = edge_items;
+        self
+    }
+
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.options.precision = precision;
+        self
+    }
+
+    pub fn sci_mode(mut self, sci_mode: bool) -> Self {
+        self.options.sci_mode = sci_mode;
+        self
+    }
+
+    pub fn line_width(mut self, line_width: usize) -> Self {
+        self.options.line_width = line_width;
+        self
+    }
+
+    /// Installs these options as the global defaults used by `Display`.
+    pub fn apply(self) {
+        set_print_options(self.options)
+    }
+}
+
+static PRINT_OPTIONS: OnceLock<RwLock<PrintOptions>> = OnceLock::new();
+
+fn print_options_lock() -> &'static RwLock<PrintOptions> {
+    PRINT_OPTIONS.get_or_init(|| RwLock::new(PrintOptions::default()))
+}
+
+/// Returns the global print options currently used by `Tensor`'s `Display`.
+pub fn print_options() -> PrintOptions {
+    *print_options_lock().read().unwrap()
+}
+
+/// Installs new global print options, used by `Tensor`'s `Display` impl.
+pub fn set_print_options(options: PrintOptions) {
+    *print_options_lock().write().unwrap() = options;
+}
+
+fn format_scalar(tensor: &Tensor, opts: &PrintOptions) -> String {
+    match tensor.kind() {
+        Kind::Int | Kind::Int8 | Kind::Uint8 | Kind::Int16 | Kind::Int64 => {
+            format!("{}", i64::from(tensor))
+        }
+        Kind::Half | Kind::Float | Kind::Double => {
+            let v = f64::from(tensor);
+            if opts.sci_mode {
+                format!("{:.*e}", opts.precision, v)
+            } else {
+                format!("{:.*}", opts.precision, v)
+            }
+        }
+        kind => format!("{:?}", kind),
+    }
+}
+
+/// The indices of a dimension to actually print, with `None` standing in
+/// for the elided middle section of a summarized dimension.
+fn dim_indices(dim_len: i64, opts: &PrintOptions, summarize: bool) -> Vec<Option<i64>> {
+    if !summarize {
+        return (0..dim_len).map(Some).collect();
+    }
+    let edge_items = opts.edge_items as i64;
+    let mut indices: Vec<Option<i64>> = (0..edge_items).map(Some).collect();
+    indices.push(None);
+    indices.extend((dim_len - edge_items..dim_len).map(Some));
+    indices
+}
+
+// `summarize` reflects whether the *original* tensor (not the current
+// recursive sub-tensor) is over `threshold`: it is decided once in
+// `Display::fmt` and threaded down unchanged, so a summarized outer axis
+// doesn't stop later, smaller axes from also being edge-truncated.
+fn fmt_rec(
+    tensor: &Tensor,
+    opts: &PrintOptions,
+    summarize: bool,
+    indent: usize,
+    f: &mut fmt::Formatter,
+) -> fmt::Result {
+    let shape = tensor.size();
+    if shape.is_empty() {
+        return write!(f, "{}", format_scalar(tensor, opts));
+    }
+    let dim_len = shape[0];
+    // `summarize` (whether the *original* tensor is over threshold) is
+    // threaded through unchanged; only the per-axis truncation decision
+    // depends on this particular dimension's length.
+    let truncate_axis = summarize && dim_len > 2 * opts.edge_items as i64;
+    let indices = dim_indices(dim_len, opts, truncate_axis);
+    let is_innermost = shape.len() == 1;
+
+    write!(f, "[")?;
+    let mut line_len = indent + 1;
+    for (i, index) in indices.iter().enumerate() {
+        if i > 0 {
+            if is_innermost {
+                write!(f, ", ")?;
+                line_len += 2;
+                if line_len >= opts.line_width {
+                    write!(f, "\n{}", " ".repeat(indent + 1))?;
+                    line_len = indent + 1;
+                }
+            } else {
+                write!(f, "\n{}", " ".repeat(indent + 1))?;
+                line_len = indent + 1;
+            }
+        }
+        match index {
+            None => {
+                write!(f, "...")?;
+                line_len += 3;
+            }
+            Some(idx) => {
+                let rendered = if is_innermost {
+                    format_scalar(&tensor.get(*idx), opts)
+                } else {
+                    String::new()
+                };
+                if is_innermost {
+                    write!(f, "{}", rendered)?;
+                    line_len += rendered.len();
+                } else {
+                    fmt_rec(&tensor.get(*idx), opts, summarize, indent + 1, f)?;
+                }
+            }
+        }
+    }
+    write!(f, "]")
+}
+
+impl fmt::Display for Tensor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let opts = print_options();
+        let summarize = self.numel() as usize > opts.threshold;
+        fmt_rec(self, &opts, summarize, 0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inner_dimensions_are_summarized_too() {
+        // 50x50 is over the default threshold (1000) but each row, taken on
+        // its own, has only 50 elements -- well under it. If `summarize`
+        // were (re-)decided from each sub-tensor instead of the root, these
+        // rows would print in full instead of being edge-truncated.
+        let t = Tensor::of_slice(&(0..2500i64).collect::<Vec<_>>()).view(&[50, 50]);
+        let rendered = format!("{}", t);
+        // One `...` for the elided rows, plus one per displayed row (there
+        // are 2 * edge_items of those) for their own elided columns.
+        let expected_ellipses = 1 + 2 * PrintOptions::default().edge_items;
+        assert_eq!(rendered.matches("...").count(), expected_ellipses);
+    }
+}
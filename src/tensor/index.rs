@@ -0,0 +1,247 @@
+//! Numpy/PyTorch-style advanced indexing for tensors.
+//!
+//! This lets a single `.i(...)` call replace a chain of manual `narrow` /
+//! `index_select` / `unsqueeze` calls, e.g. `t.i((.., 3, ..))`,
+//! `t.i((NewAxis, 1..4))`, or `t.i((Ellipsis, 3))` on a tensor of any rank.
+use super::Tensor;
+use std::ops::{Range, RangeFrom, RangeFull, RangeTo};
+
+/// A marker type used to insert a new length-1 dimension, mirroring `None`
+/// in NumPy/PyTorch fancy indexing.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct NewAxis;
+
+/// A marker type standing in for as many full-range (`..`) dimensions as
+/// needed to make the rest of the index tuple cover every dimension of the
+/// tensor, mirroring Python/NumPy's `...`. At most one may appear in an
+/// index tuple.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Ellipsis;
+
+/// A single indexing operation on one dimension of a tensor.
+#[derive(Debug, PartialEq)]
+pub enum TensorIndexer {
+    Select(i64),
+    Narrow(Option<i64>, Option<i64>),
+    IndexSelect(Tensor),
+    InsertNewAxis,
+    InsertEllipsis,
+}
+
+impl From<i64> for TensorIndexer {
+    fn from(index: i64) -> Self {
+        TensorIndexer::Select(index)
+    }
+}
+
+impl From<Range<i64>> for TensorIndexer {
+    fn from(range: Range<i64>) -> Self {
+        TensorIndexer::Narrow(Some(range.start), Some(range.end))
+    }
+}
+
+impl From<RangeFrom<i64>> for TensorIndexer {
+    fn from(range: RangeFrom<i64>) -> Self {
+        TensorIndexer::Narrow(Some(range.start), None)
+    }
+}
+
+impl From<RangeTo<i64>> for TensorIndexer {
+    fn from(range: RangeTo<i64>) -> Self {
+        TensorIndexer::Narrow(None, Some(range.end))
+    }
+}
+
+impl From<RangeFull> for TensorIndexer {
+    fn from(_: RangeFull) -> Self {
+        TensorIndexer::Narrow(None, None)
+    }
+}
+
+impl From<NewAxis> for TensorIndexer {
+    fn from(_: NewAxis) -> Self {
+        TensorIndexer::InsertNewAxis
+    }
+}
+
+impl From<Ellipsis> for TensorIndexer {
+    fn from(_: Ellipsis) -> Self {
+        TensorIndexer::InsertEllipsis
+    }
+}
+
+impl From<&Tensor> for TensorIndexer {
+    fn from(tensor: &Tensor) -> Self {
+        TensorIndexer::IndexSelect(tensor.shallow_clone())
+    }
+}
+
+impl Tensor {
+    fn indexer(&self, index_spec: &[TensorIndexer]) -> Tensor {
+        // Every indexer except `NewAxis` (which inserts a dimension) and
+        // `Ellipsis` itself (whose width depends on this count) consumes
+        // one dimension of the tensor being indexed.
+        let consumed_dims = index_spec
+            .iter()
+            .filter(|spec| {
+                !matches!(spec, TensorIndexer::InsertNewAxis | TensorIndexer::InsertEllipsis)
+            })
+            .count();
+        let ellipsis_count = index_spec
+            .iter()
+            .filter(|spec| matches!(spec, TensorIndexer::InsertEllipsis))
+            .count();
+        if ellipsis_count > 1 {
+            panic!("only a single Ellipsis is allowed per index, got {}", ellipsis_count)
+        }
+        let ellipsis_fill = if ellipsis_count == 1 {
+            self.size().len().saturating_sub(consumed_dims)
+        } else {
+            0
+        };
+
+        let mut curr_tensor = self.shallow_clone();
+        let mut curr_dim = 0;
+        for spec in index_spec.iter() {
+            curr_tensor = match spec {
+                TensorIndexer::InsertEllipsis => {
+                    let mut t = curr_tensor;
+                    for _ in 0..ellipsis_fill {
+                        let dim_len = t.size()[curr_dim as usize];
+                        t = t.narrow(curr_dim, 0, dim_len);
+                        curr_dim += 1;
+                    }
+                    t
+                }
+                TensorIndexer::InsertNewAxis => {
+                    let t = curr_tensor.unsqueeze(curr_dim);
+                    curr_dim += 1;
+                    t
+                }
+                TensorIndexer::Select(index) => curr_tensor.select(curr_dim, *index),
+                TensorIndexer::Narrow(start, end) => {
+                    let dim_len = curr_tensor.size()[curr_dim as usize];
+                    let start = start.unwrap_or(0);
+                    let end = end.unwrap_or(dim_len);
+                    let t = curr_tensor.narrow(curr_dim, start, end - start);
+                    curr_dim += 1;
+                    t
+                }
+                TensorIndexer::IndexSelect(index) => {
+                    let t = curr_tensor.index_select(curr_dim, index);
+                    curr_dim += 1;
+                    t
+                }
+            };
+        }
+        curr_tensor
+    }
+}
+
+/// Applies Python/NumPy-style advanced indexing to a tensor, as in `t.i(3)`
+/// or `t.i((.., 3, NewAxis))`. Each element of the index tuple advances one
+/// dimension of the tensor, except for [`NewAxis`] which inserts a dimension
+/// without consuming one, and [`Ellipsis`] which expands to as many
+/// full-range dimensions as needed to cover the tensor's remaining rank, so
+/// e.g. `t.i((Ellipsis, 3))` selects index `3` on the last dimension of a
+/// tensor of any rank.
+pub trait IndexOp<T> {
+    fn i(&self, index: T) -> Tensor;
+}
+
+impl<T> IndexOp<T> for Tensor
+where
+    T: Into<TensorIndexer>,
+{
+    fn i(&self, index: T) -> Tensor {
+        self.indexer(&[index.into()])
+    }
+}
+
+macro_rules! index_impl {
+    ($($t:ident),+) => {
+        impl<$($t),+> IndexOp<($($t,)+)> for Tensor
+        where
+            $($t: Into<TensorIndexer>,)+
+        {
+            #[allow(non_snake_case)]
+            fn i(&self, index: ($($t,)+)) -> Tensor {
+                let ($($t,)+) = index;
+                self.indexer(&[$($t.into()),+])
+            }
+        }
+    };
+}
+
+index_impl!(A, B);
+index_impl!(A, B, C);
+index_impl!(A, B, C, D);
+index_impl!(A, B, C, D, E);
+index_impl!(A, B, C, D, E, F);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Kind;
+
+    fn arange(n: i64) -> Tensor {
+        Tensor::of_slice(&(0..n).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn select_drops_a_dimension() {
+        let t = arange(12).view(&[3, 4]);
+        assert_eq!(t.i(1).size(), vec![4]);
+    }
+
+    #[test]
+    fn range_narrows_without_dropping() {
+        let t = arange(12).view(&[3, 4]);
+        assert_eq!(t.i(1..3).size(), vec![2, 4]);
+    }
+
+    #[test]
+    fn new_axis_inserts_without_consuming() {
+        let t = arange(12).view(&[3, 4]);
+        assert_eq!(t.i((NewAxis, 1)).size(), vec![1, 4]);
+    }
+
+    #[test]
+    fn tensor_index_select() {
+        let t = arange(12).view(&[3, 4]);
+        let idx = Tensor::of_slice(&[0i64, 2]);
+        assert_eq!(t.i(&idx).size(), vec![2, 4]);
+    }
+
+    #[test]
+    fn ellipsis_fills_remaining_dimensions() {
+        let t = arange(24).view(&[2, 3, 4]);
+        assert_eq!(t.i((Ellipsis, 1)).size(), vec![2, 3]);
+        assert_eq!(t.i((0, Ellipsis)).size(), vec![3, 4]);
+    }
+
+    #[test]
+    fn ellipsis_on_rank_one_is_a_no_op() {
+        let t = arange(4);
+        assert_eq!(t.i(Ellipsis).size(), vec![4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "only a single Ellipsis")]
+    fn rejects_more_than_one_ellipsis() {
+        let t = arange(24).view(&[2, 3, 4]);
+        let _ = t.i((Ellipsis, Ellipsis));
+    }
+
+    #[test]
+    fn combination_matches_manual_ops() {
+        let t = arange(24).view(&[2, 3, 4]);
+        // `Select` collapses its dimension, so the following `Narrow` lands
+        // on what was dimension 1 before the select, now shifted to 0.
+        let got = t.i((1, 1..3, NewAxis, 2));
+        let want = t.select(0, 1).narrow(0, 1, 2).unsqueeze(1).select(2, 2);
+        assert_eq!(got.kind(), Kind::Int64);
+        assert_eq!(got.size(), want.size());
+        assert_eq!(got, want);
+    }
+}
@@ -3,10 +3,19 @@ use crate::{Device, Kind};
 use failure::Fallible;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
+mod autocast;
+mod blob;
+pub mod display;
+mod index;
 mod iter;
 mod npy;
+mod safetensors;
 
 pub use super::wrappers::tensor::{no_grad, no_grad_guard, NoGradGuard, Reduction, Tensor};
+pub use autocast::{autocast, autocast_guard, AutocastGuard};
+pub use blob::BorrowedTensor;
+pub use display::{set_print_options, PrintOptions};
+pub use index::{Ellipsis, IndexOp, NewAxis, TensorIndexer};
 
 macro_rules! impl_op {
     ($trait:ident, $rhs:ident, $func:ident, $op:ident) => {
@@ -234,6 +243,31 @@ impl Tensor {
     pub fn nll_loss(&self, targets: &Tensor) -> Tensor {
         self.g_nll_loss::<Tensor>(targets, None, Reduction::Mean, -100)
     }
+
+    /// Softmax with an extra implicit "null" logit of zero in the
+    /// denominator, i.e. `softmax` computed over the inputs concatenated
+    /// with a zero column and then dropping that column. Unlike ordinary
+    /// softmax the output is allowed to sum to less than one, so attention
+    /// heads are not forced to attend when nothing is relevant.
+    ///
+    /// Computed as `exp(x_i - m) / (exp(-m) + sum_j exp(x_j - m))` with
+    /// `m = max(max_j x_j, 0)` for numerical stability.
+    pub fn quiet_softmax(&self, dim: i64) -> Tensor {
+        let m = self.max1(dim, true).0.clamp_min(0.);
+        let exp = (self - &m).exp();
+        let denom = (-&m).exp() + exp.sum1(&[dim], true, self.kind());
+        exp / denom
+    }
+
+    /// The log-space counterpart of [`Tensor::quiet_softmax`], analogous to
+    /// how [`Tensor::log_softmax`] relates to `softmax`. Suitable for
+    /// feeding into `nll_loss`-style losses.
+    pub fn quiet_log_softmax(&self, dim: i64) -> Tensor {
+        let m = self.max1(dim, true).0.clamp_min(0.);
+        let shifted = self - &m;
+        let denom = (-&m).exp() + shifted.exp().sum1(&[dim], true, self.kind());
+        shifted - denom.log()
+    }
 }
 
 macro_rules! from_tensor {
@@ -433,3 +467,38 @@ impl PartialEq for Tensor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &Tensor, b: &Tensor, eps: f64) {
+        let a = Vec::<f64>::from(a);
+        let b = Vec::<f64>::from(b);
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < eps, "{} vs {}", x, y);
+        }
+    }
+
+    #[test]
+    fn quiet_softmax_matches_naive_formula() {
+        let x = Tensor::of_slice(&[0.1f64, -0.3, 0.5, 1.2]).view(&[2, 2]);
+        let exp = x.exp();
+        let naive = &exp / (exp.sum1(&[1], true, Kind::Double) + 1.0);
+        assert_close(&x.quiet_softmax(1), &naive, 1e-6);
+    }
+
+    #[test]
+    fn quiet_softmax_rows_can_sum_to_less_than_one() {
+        let x = Tensor::of_slice(&[-50f64, -50., -50., -50.]).view(&[1, 4]);
+        let row_sum = f64::from(x.quiet_softmax(1).sum1(&[1], false, Kind::Double));
+        assert!(row_sum < 1.0);
+    }
+
+    #[test]
+    fn quiet_log_softmax_is_the_log_of_quiet_softmax() {
+        let x = Tensor::of_slice(&[0.1f64, -0.3, 0.5, 1.2]).view(&[2, 2]);
+        assert_close(&x.quiet_log_softmax(1), &x.quiet_softmax(1).log(), 1e-6);
+    }
+}
@@ -0,0 +1,456 @@
+//! Support for the safetensors serialization format.
+//!
+//! A file is a little-endian `u64` byte length, a JSON header of that many
+//! bytes mapping each tensor name to its dtype/shape/`[begin, end)` byte
+//! offsets, and then the raw tensor bytes those offsets point into.
+use crate::{Device, Kind, Tensor};
+use failure::{bail, Fallible};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+fn kind_to_dtype(kind: Kind) -> Fallible<&'static str> {
+    match kind {
+        Kind::Half => Ok("F16"),
+        Kind::Float => Ok("F32"),
+        Kind::Double => Ok("F64"),
+        Kind::Int8 => Ok("I8"),
+        Kind::Uint8 => Ok("U8"),
+        Kind::Int16 => Ok("I16"),
+        Kind::Int => Ok("I32"),
+        Kind::Int64 => Ok("I64"),
+        kind => bail!("unsupported kind for safetensors {:?}", kind),
+    }
+}
+
+fn dtype_to_kind(dtype: &str) -> Fallible<Kind> {
+    match dtype {
+        "F16" => Ok(Kind::Half),
+        "F32" => Ok(Kind::Float),
+        "F64" => Ok(Kind::Double),
+        "I8" => Ok(Kind::Int8),
+        "U8" => Ok(Kind::Uint8),
+        "I16" => Ok(Kind::Int16),
+        "I32" => Ok(Kind::Int),
+        "I64" => Ok(Kind::Int64),
+        dtype => bail!("unsupported safetensors dtype {}", dtype),
+    }
+}
+
+fn element_size(kind: Kind) -> Fallible<usize> {
+    match kind {
+        Kind::Half | Kind::Int16 => Ok(2),
+        Kind::Float | Kind::Int => Ok(4),
+        Kind::Double | Kind::Int64 => Ok(8),
+        Kind::Int8 | Kind::Uint8 => Ok(1),
+        kind => bail!("unsupported kind for safetensors {:?}", kind),
+    }
+}
+
+fn tensor_bytes(tensor: &Tensor) -> Fallible<Vec<u8>> {
+    // `data_ptr` is a host pointer: the tensor must be moved to the CPU
+    // before it is dereferenced, or this is a host-side read of device
+    // memory.
+    let tensor = tensor.to(Device::Cpu).contiguous();
+    let elt_size = element_size(tensor.kind())?;
+    let mut buffer = vec![0u8; tensor.numel() as usize * elt_size];
+    unsafe {
+        std::ptr::copy_nonoverlapping(
+            tensor.data_ptr() as *const u8,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+        );
+    }
+    Ok(buffer)
+}
+
+fn tensor_of_bytes(data: &[u8], kind: Kind, shape: &[i64]) -> Fallible<Tensor> {
+    if let Some(d) = shape.iter().find(|d| **d < 0) {
+        bail!("safetensors: invalid negative dimension {} in shape {:?}", d, shape)
+    }
+    let elt_size = element_size(kind)?;
+    let numel: i64 = shape.iter().product();
+    if data.len() != numel as usize * elt_size {
+        bail!(
+            "safetensors: unexpected byte length {}, expected {} for shape {:?} and kind {:?}",
+            data.len(),
+            numel as usize * elt_size,
+            shape,
+            kind
+        )
+    }
+    let tensor = Tensor::zeros(shape, (kind, Device::Cpu));
+    unsafe {
+        std::ptr::copy_nonoverlapping(data.as_ptr(), tensor.data_ptr() as *mut u8, data.len());
+    }
+    Ok(tensor)
+}
+
+fn escape_json(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// A tiny, schema-specific JSON reader: we only ever need to parse objects,
+// arrays of small integers, and strings, so a full JSON crate would be
+// overkill for decoding a safetensors header.
+struct JsonReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+enum JsonValue {
+    String(String),
+    Number(i64),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl<'a> JsonReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        JsonReader { data, pos: 0 }
+    }
+
+    fn skip_ws(&mut self) {
+        while self.pos < self.data.len() && self.data[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Fallible<()> {
+        self.skip_ws();
+        if self.data.get(self.pos) != Some(&c) {
+            bail!("safetensors: expected {:?} at offset {}", c as char, self.pos)
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn parse_string(&mut self) -> Fallible<String> {
+        self.expect(b'"')?;
+        // Collect raw bytes rather than decoding byte-by-byte: a tensor
+        // name can contain multi-byte UTF-8 sequences, and only `"`/`\`
+        // are ever escaped in the headers we emit, so every other byte
+        // (including continuation bytes) must pass through untouched and
+        // get decoded together at the end.
+        let mut bytes = Vec::new();
+        loop {
+            let c = *self
+                .data
+                .get(self.pos)
+                .ok_or_else(|| failure::format_err!("safetensors: unterminated string"))?;
+            self.pos += 1;
+            match c {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = *self
+                        .data
+                        .get(self.pos)
+                        .ok_or_else(|| failure::format_err!("safetensors: unterminated escape"))?;
+                    self.pos += 1;
+                    bytes.push(escaped);
+                }
+                c => bytes.push(c),
+            }
+        }
+        String::from_utf8(bytes)
+            .map_err(|_| failure::format_err!("safetensors: string is not valid utf-8"))
+    }
+
+    fn parse_number(&mut self) -> Fallible<i64> {
+        let start = self.pos;
+        if self.data.get(self.pos) == Some(&b'-') {
+            self.pos += 1;
+        }
+        while self.data.get(self.pos).map_or(false, u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.data[start..self.pos])?
+            .parse()
+            .map_err(|_| failure::format_err!("safetensors: invalid number"))
+    }
+
+    fn parse_array(&mut self) -> Fallible<Vec<JsonValue>> {
+        self.expect(b'[')?;
+        let mut out = vec![];
+        self.skip_ws();
+        if self.data.get(self.pos) == Some(&b']') {
+            self.pos += 1;
+            return Ok(out);
+        }
+        loop {
+            out.push(self.parse_value()?);
+            self.skip_ws();
+            match self.data.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => bail!("safetensors: malformed array"),
+            }
+        }
+        Ok(out)
+    }
+
+    fn parse_object(&mut self) -> Fallible<Vec<(String, JsonValue)>> {
+        self.expect(b'{')?;
+        let mut out = vec![];
+        self.skip_ws();
+        if self.data.get(self.pos) == Some(&b'}') {
+            self.pos += 1;
+            return Ok(out);
+        }
+        loop {
+            self.skip_ws();
+            let key = self.parse_string()?;
+            self.expect(b':')?;
+            let value = self.parse_value()?;
+            out.push((key, value));
+            self.skip_ws();
+            match self.data.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => bail!("safetensors: malformed object"),
+            }
+        }
+        Ok(out)
+    }
+
+    fn parse_value(&mut self) -> Fallible<JsonValue> {
+        self.skip_ws();
+        match self.data.get(self.pos) {
+            Some(b'"') => Ok(JsonValue::String(self.parse_string()?)),
+            Some(b'[') => Ok(JsonValue::Array(self.parse_array()?)),
+            Some(b'{') => Ok(JsonValue::Object(self.parse_object()?)),
+            Some(_) => Ok(JsonValue::Number(self.parse_number()?)),
+            None => bail!("safetensors: unexpected end of header"),
+        }
+    }
+}
+
+struct TensorInfo {
+    dtype: String,
+    shape: Vec<i64>,
+    data_offsets: (usize, usize),
+}
+
+fn parse_header(header: &[u8]) -> Fallible<Vec<(String, TensorInfo)>> {
+    let root = JsonReader::new(header).parse_object()?;
+    let mut infos = vec![];
+    for (name, value) in root {
+        if name == "__metadata__" {
+            continue;
+        }
+        let fields = match value {
+            JsonValue::Object(fields) => fields,
+            _ => bail!("safetensors: expected an object for tensor {}", name),
+        };
+        let mut dtype = None;
+        let mut shape = None;
+        let mut data_offsets = None;
+        for (key, value) in fields {
+            match key.as_str() {
+                "dtype" => {
+                    dtype = Some(match value {
+                        JsonValue::String(s) => s,
+                        _ => bail!("safetensors: dtype must be a string"),
+                    })
+                }
+                "shape" => {
+                    shape = Some(match value {
+                        JsonValue::Array(dims) => dims
+                            .into_iter()
+                            .map(|d| match d {
+                                JsonValue::Number(n) => Ok(n),
+                                _ => bail!("safetensors: shape must be an array of integers"),
+                            })
+                            .collect::<Fallible<Vec<_>>>()?,
+                        _ => bail!("safetensors: shape must be an array"),
+                    })
+                }
+                "data_offsets" => {
+                    data_offsets = Some(match value {
+                        JsonValue::Array(offsets) if offsets.len() == 2 => {
+                            let get = |v: &JsonValue| match v {
+                                JsonValue::Number(n) => Ok(*n as usize),
+                                _ => bail!("safetensors: data_offsets must be integers"),
+                            };
+                            (get(&offsets[0])?, get(&offsets[1])?)
+                        }
+                        _ => bail!("safetensors: data_offsets must be a 2-element array"),
+                    })
+                }
+                _ => {}
+            }
+        }
+        infos.push((
+            name,
+            TensorInfo {
+                dtype: dtype.ok_or_else(|| failure::format_err!("safetensors: missing dtype"))?,
+                shape: shape.ok_or_else(|| failure::format_err!("safetensors: missing shape"))?,
+                data_offsets: data_offsets
+                    .ok_or_else(|| failure::format_err!("safetensors: missing data_offsets"))?,
+            },
+        ));
+    }
+    Ok(infos)
+}
+
+fn validate_offsets(infos: &[(String, TensorInfo)], data_len: usize) -> Fallible<()> {
+    let mut sorted_infos = infos.iter().collect::<Vec<_>>();
+    sorted_infos.sort_by_key(|(_, info)| info.data_offsets.0);
+    let mut cursor = 0;
+    for (name, info) in sorted_infos.iter() {
+        let (begin, end) = info.data_offsets;
+        if begin != cursor || end > data_len || begin > end {
+            bail!(
+                "safetensors: tensor {} has invalid or non-contiguous offsets {:?}",
+                name,
+                info.data_offsets
+            )
+        }
+        cursor = end;
+    }
+    if cursor != data_len {
+        bail!(
+            "safetensors: data region is {} bytes but offsets only cover {} bytes",
+            data_len,
+            cursor
+        )
+    }
+    Ok(())
+}
+
+impl Tensor {
+    /// Serializes a collection of named tensors to a file using the safetensors format.
+    pub fn save_safetensors<S: AsRef<str>, P: AsRef<Path>>(
+        named_tensors: &[(S, &Tensor)],
+        path: P,
+    ) -> Fallible<()> {
+        let mut offset = 0;
+        let mut header = String::from("{");
+        let mut buffers = vec![];
+        for (i, (name, tensor)) in named_tensors.iter().enumerate() {
+            let dtype = kind_to_dtype(tensor.kind())?;
+            let shape = tensor.size();
+            let bytes = tensor_bytes(tensor)?;
+            let begin = offset;
+            let end = offset + bytes.len();
+            offset = end;
+            if i > 0 {
+                header.push(',');
+            }
+            header.push_str(&format!(
+                "\"{}\":{{\"dtype\":\"{}\",\"shape\":[{}],\"data_offsets\":[{},{}]}}",
+                escape_json(name.as_ref()),
+                dtype,
+                shape.iter().map(i64::to_string).collect::<Vec<_>>().join(","),
+                begin,
+                end,
+            ));
+            buffers.push(bytes);
+        }
+        header.push('}');
+        let mut file = File::create(path)?;
+        file.write_all(&(header.len() as u64).to_le_bytes())?;
+        file.write_all(header.as_bytes())?;
+        for bytes in buffers {
+            file.write_all(&bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Serializes a map of named tensors to a file using the safetensors format.
+    pub fn save_safetensors_map<P: AsRef<Path>>(
+        named_tensors: &HashMap<String, Tensor>,
+        path: P,
+    ) -> Fallible<()> {
+        let named_tensors: Vec<_> = named_tensors.iter().map(|(k, v)| (k.as_str(), v)).collect();
+        Tensor::save_safetensors(&named_tensors, path)
+    }
+
+    /// Reads a safetensors file and returns the named tensors that it contains.
+    pub fn load_safetensors<P: AsRef<Path>>(path: P) -> Fallible<Vec<(String, Tensor)>> {
+        let mut file = File::open(path)?;
+        let mut header_len_bytes = [0u8; 8];
+        file.read_exact(&mut header_len_bytes)?;
+        let header_len = u64::from_le_bytes(header_len_bytes) as usize;
+        let mut header_bytes = vec![0u8; header_len];
+        file.read_exact(&mut header_bytes)?;
+        let infos = parse_header(&header_bytes)?;
+        let mut data = vec![];
+        file.read_to_end(&mut data)?;
+        validate_offsets(&infos, data.len())?;
+        infos
+            .into_iter()
+            .map(|(name, info)| {
+                let kind = dtype_to_kind(&info.dtype)?;
+                let (begin, end) = info.data_offsets;
+                let tensor = tensor_of_bytes(&data[begin..end], kind, &info.shape)?;
+                Ok((name, tensor))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("tch-safetensors-test-{}-{}", std::process::id(), name));
+        path
+    }
+
+    #[test]
+    fn save_load_round_trip() {
+        let path = temp_path("round-trip.safetensors");
+        let a = Tensor::of_slice(&[1f32, 2., 3., 4.]).view(&[2, 2]);
+        let b = Tensor::of_slice(&[1i64, 2, 3]);
+        // A multi-byte UTF-8 tensor name exercises the header string parser.
+        let name = "poids\u{e9}";
+        Tensor::save_safetensors(&[(name, &a), ("b", &b)], &path).unwrap();
+        let loaded: HashMap<_, _> = Tensor::load_safetensors(&path).unwrap().into_iter().collect();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded[name], a);
+        assert_eq!(loaded["b"], b);
+    }
+
+    #[test]
+    fn rejects_negative_shape() {
+        let err = tensor_of_bytes(&[0u8; 4], Kind::Float, &[-1, 4]).unwrap_err();
+        assert!(err.to_string().contains("negative dimension"));
+    }
+
+    #[test]
+    fn rejects_overlapping_offsets() {
+        let infos = parse_header(
+            br#"{"a":{"dtype":"F32","shape":[1],"data_offsets":[0,4]},
+                 "b":{"dtype":"F32","shape":[1],"data_offsets":[0,4]}}"#,
+        )
+        .unwrap();
+        assert!(validate_offsets(&infos, 4).is_err());
+    }
+
+    #[test]
+    fn rejects_gap_before_end_of_data() {
+        let infos = parse_header(br#"{"a":{"dtype":"F32","shape":[1],"data_offsets":[0,4]}}"#)
+            .unwrap();
+        assert!(validate_offsets(&infos, 8).is_err());
+    }
+}